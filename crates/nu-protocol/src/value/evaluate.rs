@@ -1,16 +1,52 @@
 use crate::value::Value;
 use indexmap::IndexMap;
 use std::fmt::Debug;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Scope {
     it: Value,
     vars: IndexMap<String, Value>,
+    parent: Option<Rc<Scope>>,
+}
+
+impl Scope {
+    pub fn new(it: Value) -> Scope {
+        Scope {
+            it,
+            vars: IndexMap::new(),
+            parent: None,
+        }
+    }
+
+    /// Builds a fresh inner frame on top of `self` instead of mutating the
+    /// shared var map: `vars` (and `it`) are visible only within the new
+    /// frame and shadow any outer binding of the same name, while lookups
+    /// that miss here still fall through to `self`.
+    pub fn child(self: &Rc<Scope>, it: Value, vars: IndexMap<String, Value>) -> Scope {
+        Scope {
+            it,
+            vars,
+            parent: Some(self.clone()),
+        }
+    }
+
+    pub fn it(&self) -> &Value {
+        &self.it
+    }
+
+    /// Resolves `name` by walking from this frame outward, so an inner
+    /// binding shadows an outer one of the same name.
+    pub fn get_var(&self, name: &str) -> Option<&Value> {
+        self.vars
+            .get(name)
+            .or_else(|| self.parent.as_ref().and_then(|parent| parent.get_var(name)))
+    }
 }
 
 #[typetag::serde(tag = "type")]
 pub trait Evaluate: Debug + Send {
-    fn evaluate(&self, scope: &Scope) -> Value;
+    fn evaluate(&self, scope: &Rc<Scope>) -> Value;
     fn clone_box(&self) -> Box<dyn Evaluate>;
 }
 
@@ -3,15 +3,126 @@ use crate::prelude::*;
 use bytes::{BufMut, BytesMut};
 use futures::stream::StreamExt;
 use futures_codec::{Decoder, Encoder, Framed};
+use glob::glob;
 use log::{log_enabled, trace};
 use nu_errors::ShellError;
 use nu_parser::{ExternalCommand, InternalCommand};
 use nu_protocol::{CommandAction, Primitive, ReturnSuccess, UntaggedValue, Value};
 use nu_source::PrettyDebug;
-use std::io::{Error, ErrorKind};
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
 use subprocess::Exec;
 
-/// A simple `Codec` implementation that splits up data into lines.
+/// The shape to render piped rows into on an external command's stdin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StdinFormat {
+    /// One row per line: strings pass through verbatim, anything else falls
+    /// back to its JSON representation.
+    Lines,
+    /// Each row rendered as a standalone JSON value.
+    Json,
+    /// Each row's columns rendered tab-separated; non-row values fall back
+    /// to `Lines` rendering.
+    Tsv,
+}
+
+impl Default for StdinFormat {
+    fn default() -> StdinFormat {
+        StdinFormat::Lines
+    }
+}
+
+/// Renders a single pipeline row the way an external process expects it on
+/// stdin, in the caller-selected `format`.
+pub(crate) fn render_stdin_row(value: &Value, format: StdinFormat) -> String {
+    match format {
+        StdinFormat::Json => serde_json::to_string(&value.value).unwrap_or_default(),
+        StdinFormat::Tsv => match &value.value {
+            UntaggedValue::Row(row) => row
+                .entries
+                .values()
+                .map(|cell| render_stdin_row(cell, StdinFormat::Lines))
+                .collect::<Vec<_>>()
+                .join("\t"),
+            _ => render_stdin_row(value, StdinFormat::Lines),
+        },
+        StdinFormat::Lines => match &value.value {
+            UntaggedValue::Primitive(Primitive::String(s)) => s.clone(),
+            _ => serde_json::to_string(&value.value).unwrap_or_default(),
+        },
+    }
+}
+
+/// Spawns a background thread that drains `objects` as it arrives and
+/// serializes each row onto a pipe in `format`, handing back the read end.
+/// The upstream stream is consumed incrementally rather than collected up
+/// front, so an unbounded producer can feed the child without buffering the
+/// whole pipeline in memory; writing on a separate thread keeps a full pipe
+/// buffer from deadlocking the stream.
+pub(crate) fn spawn_stream_stdin_writer(
+    mut objects: InputStream,
+    format: StdinFormat,
+) -> std::io::Result<std::fs::File> {
+    let (reader, mut writer) = os_pipe::pipe()?;
+
+    std::thread::spawn(move || {
+        futures::executor::block_on(async {
+            while let Some(value) = objects.next().await {
+                let _ = writeln!(writer, "{}", render_stdin_row(&value, format));
+            }
+        });
+    });
+
+    Ok(reader.into())
+}
+
+/// Unquoted `*`, `?` and `[...]` are the only glob metacharacters we honor; a
+/// quoted arg is always treated as literal, so callers should check for that
+/// before calling this.
+pub(crate) fn has_glob_metacharacters(arg: &str) -> bool {
+    arg.chars().any(|c| c == '*' || c == '?' || c == '[')
+}
+
+/// Expands a single glob arg against `cwd`, pushing every match onto
+/// `process` as its own argument. Resolves lazily (the `glob` iterator is
+/// walked as we push, never collected up front) and, matching shell
+/// nullglob-off behavior, falls back to the literal arg when nothing matches.
+pub(crate) fn push_glob_expanded_arg(mut process: Exec, arg: &str, cwd: &Path) -> Exec {
+    if !has_glob_metacharacters(arg) {
+        return process.arg(arg);
+    }
+
+    let pattern = cwd.join(arg);
+    let matches = match glob(&pattern.to_string_lossy()) {
+        Ok(paths) => paths,
+        Err(_) => return process.arg(arg),
+    };
+
+    let mut matched_any = false;
+    for entry in matches.filter_map(Result::ok) {
+        matched_any = true;
+        let relative = entry.strip_prefix(cwd).unwrap_or(&entry);
+        process = process.arg(relative.to_string_lossy().into_owned());
+    }
+
+    if matched_any {
+        process
+    } else {
+        process.arg(arg)
+    }
+}
+
+/// A line of output from an external command, decoded as text where
+/// possible and passed through as raw bytes where it isn't.
+#[derive(Debug)]
+pub enum StreamedLine {
+    Line(String),
+    Binary(Vec<u8>),
+}
+
+/// A simple `Codec` implementation that splits up data into lines, falling
+/// back to raw bytes for chunks that aren't valid UTF-8 instead of aborting
+/// the stream.
 pub struct LinesCodec {}
 
 impl Encoder for LinesCodec {
@@ -25,24 +136,19 @@ impl Encoder for LinesCodec {
 }
 
 impl Decoder for LinesCodec {
-    type Item = String;
+    type Item = StreamedLine;
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match src.iter().position(|b| b == &b'\n') {
-            Some(pos) if !src.is_empty() => {
-                let buf = src.split_to(pos + 1);
-                String::from_utf8(buf.to_vec())
-                    .map(Some)
-                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))
-            }
-            _ if !src.is_empty() => {
-                let drained = src.take();
-                String::from_utf8(drained.to_vec())
-                    .map(Some)
-                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))
-            }
-            _ => Ok(None),
+        let chunk = match src.iter().position(|b| b == &b'\n') {
+            Some(pos) if !src.is_empty() => src.split_to(pos + 1),
+            _ if !src.is_empty() => src.take(),
+            _ => return Ok(None),
+        };
+
+        match String::from_utf8(chunk.to_vec()) {
+            Ok(line) => Ok(Some(StreamedLine::Line(line))),
+            Err(_) => Ok(Some(StreamedLine::Binary(chunk.to_vec()))),
         }
     }
 }
@@ -50,6 +156,13 @@ impl Decoder for LinesCodec {
 pub(crate) struct ClassifiedInputStream {
     pub(crate) objects: InputStream,
     pub(crate) stdin: Option<std::fs::File>,
+    /// Whether `objects` is real upstream data (piped in from a previous
+    /// command) as opposed to the single `Nothing` placeholder `new()` seeds
+    /// a bare, first-in-pipeline invocation with. External commands must not
+    /// treat the latter as something to pipe into the child's stdin, or a
+    /// bare interactive `vim`/`less`/`fzf` run at the REPL would get a pipe
+    /// instead of the real terminal.
+    pub(crate) has_upstream: bool,
 }
 
 impl ClassifiedInputStream {
@@ -57,6 +170,7 @@ impl ClassifiedInputStream {
         ClassifiedInputStream {
             objects: vec![value::nothing().into_value(Tag::unknown())].into(),
             stdin: None,
+            has_upstream: false,
         }
     }
 
@@ -64,6 +178,7 @@ impl ClassifiedInputStream {
         ClassifiedInputStream {
             objects: stream.into(),
             stdin: None,
+            has_upstream: true,
         }
     }
 
@@ -71,6 +186,7 @@ impl ClassifiedInputStream {
         ClassifiedInputStream {
             objects: VecDeque::new().into(),
             stdin: Some(stdout),
+            has_upstream: true,
         }
     }
 }
@@ -209,12 +325,12 @@ pub(crate) async fn run_external_command(
     context: &mut Context,
     input: ClassifiedInputStream,
     stream_next: StreamNext,
+    stdin_format: StdinFormat,
 ) -> Result<ClassifiedInputStream, ShellError> {
     let stdin = input.stdin;
-    let inputs: Vec<Value> = input.objects.into_vec().await;
+    let has_upstream = input.has_upstream;
 
     trace!(target: "nu::run::external", "-> {}", command.name);
-    trace!(target: "nu::run::external", "inputs = {:?}", inputs);
 
     let mut arg_string = format!("{}", command.name);
     for arg in command.args.iter() {
@@ -223,8 +339,21 @@ pub(crate) async fn run_external_command(
 
     trace!(target: "nu::run::external", "command = {:?}", command.name);
 
+    // `$it` substitution needs every row up front to build one shelled-out
+    // command per row, so only that path collects `input.objects` into a
+    // `Vec`. Otherwise, and only when a previous command actually piped data
+    // in, we keep it as a live stream and forward it into the child's stdin
+    // as it arrives, so a huge or unbounded upstream producer never has to
+    // finish before the external command starts consuming it. A bare,
+    // first-in-pipeline invocation must keep inheriting the real stdin (the
+    // terminal, for interactive externals like `vim` or `less`).
+    let mut live_objects: Option<InputStream> = None;
+
     let mut process;
     if arg_string.contains("$it") {
+        let inputs: Vec<Value> = input.objects.into_vec().await;
+        trace!(target: "nu::run::external", "inputs = {:?}", inputs);
+
         let input_strings = inputs
             .iter()
             .map(|i| {
@@ -261,15 +390,31 @@ pub(crate) async fn run_external_command(
 
         process = Exec::shell(itertools::join(commands, " && "))
     } else {
+        if has_upstream {
+            live_objects = Some(input.objects);
+        }
+
         process = Exec::cmd(&command.name);
+        let home_dir = dirs::home_dir();
+        let cwd = context.shell_manager.path();
+        let cwd = Path::new(&cwd);
         for arg in command.args.iter() {
+            // Expand ~ before glob matching, so `~/*.txt` resolves against
+            // the real home directory instead of literally globbing for a
+            // `~` entry under cwd.
+            let arg = if let Some(ref home_dir) = home_dir {
+                arg.replace("~", home_dir.to_str().unwrap())
+            } else {
+                arg.replace("~", "~")
+            };
+
             let arg_chars: Vec<_> = arg.chars().collect();
             if arg_chars.len() > 1 && arg_chars[0] == '"' && arg_chars[arg_chars.len() - 1] == '"' {
-                // quoted string
+                // quoted string, never glob-expanded
                 let new_arg: String = arg_chars[1..arg_chars.len() - 1].iter().collect();
                 process = process.arg(new_arg);
             } else {
-                process = process.arg(arg.arg.clone());
+                process = push_glob_expanded_arg(process, &arg, cwd);
             }
         }
     }
@@ -289,6 +434,10 @@ pub(crate) async fn run_external_command(
 
     if let Some(stdin) = stdin {
         process = process.stdin(stdin);
+    } else if let Some(objects) = live_objects {
+        if let Ok(stdin) = spawn_stream_stdin_writer(objects, stdin_format) {
+            process = process.stdin(stdin);
+        }
     }
 
     trace!(target: "nu::run::external", "set up stdin pipe");
@@ -303,18 +452,27 @@ pub(crate) async fn run_external_command(
         match stream_next {
             StreamNext::Last => {
                 let _ = popen.detach();
-                loop {
-                    match popen.poll() {
-                        None => {
-                            let _ = std::thread::sleep(std::time::Duration::new(0, 100000000));
-                        }
-                        _ => {
-                            let _ = popen.terminate();
-                            break;
-                        }
+                match popen.wait() {
+                    Ok(status) if status.success() => Ok(ClassifiedInputStream::new()),
+                    // A nonzero exit is routine for plenty of externals (`grep`
+                    // with no match, `diff` with a difference), not a reason to
+                    // abort the rest of the pipeline, so report it the same way
+                    // a failed internal command does (context.error) instead of
+                    // bubbling a hard Err here.
+                    Ok(status) => {
+                        context.error(ShellError::labeled_error(
+                            format!("External command failed with {:?}", status),
+                            "command did not exit successfully",
+                            name_tag,
+                        ));
+                        Ok(ClassifiedInputStream::new())
                     }
+                    Err(err) => Err(ShellError::labeled_error(
+                        format!("External command failed to run: {}", err),
+                        "could not wait on command",
+                        name_tag,
+                    )),
                 }
-                Ok(ClassifiedInputStream::new())
             }
             StreamNext::External => {
                 let _ = popen.detach();
@@ -326,8 +484,12 @@ pub(crate) async fn run_external_command(
                 let stdout = popen.stdout.take().unwrap();
                 let file = futures::io::AllowStdIo::new(stdout);
                 let stream = Framed::new(file, LinesCodec {});
-                let stream =
-                    stream.map(move |line| value::string(line.unwrap()).into_value(&name_tag));
+                let stream = stream.map(move |line| match line.unwrap() {
+                    StreamedLine::Line(s) => value::string(s).into_value(&name_tag),
+                    StreamedLine::Binary(b) => {
+                        UntaggedValue::Primitive(Primitive::Binary(b)).into_value(&name_tag)
+                    }
+                });
                 Ok(ClassifiedInputStream::from_input_stream(
                     stream.boxed() as BoxStream<'static, Value>
                 ))
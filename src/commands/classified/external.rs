@@ -1,54 +1,24 @@
-use super::ClassifiedInputStream;
+use super::{
+    push_glob_expanded_arg, spawn_stream_stdin_writer, ClassifiedInputStream, LinesCodec,
+    StdinFormat, StreamedLine,
+};
 use crate::prelude::*;
-use bytes::{BufMut, BytesMut};
 use futures::stream::StreamExt;
-use futures_codec::{Decoder, Encoder, Framed};
+use futures_codec::Framed;
 use log::trace;
-use std::io::{Error, ErrorKind};
+use nu_protocol::Primitive;
+use std::path::Path;
 use subprocess::Exec;
 
-/// A simple `Codec` implementation that splits up data into lines.
-pub struct LinesCodec {}
-
-impl Encoder for LinesCodec {
-    type Item = String;
-    type Error = Error;
-
-    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        dst.put(item);
-        Ok(())
-    }
-}
-
-impl Decoder for LinesCodec {
-    type Item = String;
-    type Error = Error;
-
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match src.iter().position(|b| b == &b'\n') {
-            Some(pos) if !src.is_empty() => {
-                let buf = src.split_to(pos + 1);
-                String::from_utf8(buf.to_vec())
-                    .map(Some)
-                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))
-            }
-            _ if !src.is_empty() => {
-                let drained = src.take();
-                String::from_utf8(drained.to_vec())
-                    .map(Some)
-                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))
-            }
-            _ => Ok(None),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Command {
     pub(crate) name: String,
 
     pub(crate) name_tag: Tag,
     pub(crate) args: ExternalArgs,
+    /// How to render piped rows onto this command's stdin when it has no
+    /// `$it` and a previous command piped data in. Defaults to `Lines`.
+    pub(crate) stdin_format: StdinFormat,
 }
 
 impl HasSpan for Command {
@@ -88,10 +58,9 @@ impl Command {
         stream_next: StreamNext,
     ) -> Result<ClassifiedInputStream, ShellError> {
         let stdin = input.stdin;
-        let inputs: Vec<Value> = input.objects.into_vec().await;
+        let has_upstream = input.has_upstream;
 
         trace!(target: "nu::run::external", "-> {}", self.name);
-        trace!(target: "nu::run::external", "inputs = {:?}", inputs);
 
         let mut arg_string = format!("{}", self.name);
         for arg in &self.args.list {
@@ -102,8 +71,22 @@ impl Command {
 
         trace!(target: "nu::run::external", "command = {:?}", self.name);
 
+        // `$it` substitution needs every row up front to build one
+        // shelled-out command per row, so only that path collects
+        // `input.objects` into a `Vec`. Otherwise, and only when a previous
+        // command actually piped data in, we keep it as a live stream and
+        // forward it into the child's stdin as it arrives, so a huge or
+        // unbounded upstream producer never has to finish before the
+        // external command starts consuming it. A bare, first-in-pipeline
+        // invocation must keep inheriting the real stdin (the terminal, for
+        // interactive externals like `vim` or `less`).
+        let mut live_objects: Option<InputStream> = None;
+
         let mut process;
         if arg_string.contains("$it") {
+            let inputs: Vec<Value> = input.objects.into_vec().await;
+            trace!(target: "nu::run::external", "inputs = {:?}", inputs);
+
             let input_strings = inputs
                 .iter()
                 .map(|i| {
@@ -147,9 +130,15 @@ impl Command {
 
             process = Exec::shell(itertools::join(commands, " && "))
         } else {
+            if has_upstream {
+                live_objects = Some(input.objects);
+            }
+
             process = Exec::cmd(&self.name);
+            let cwd = context.shell_manager.path();
+            let cwd = Path::new(&cwd);
             for arg in &self.args.list {
-                // Let's also replace ~ as we shell out
+                // Let's also replace ~ as we shell out, before glob expansion runs
                 let arg = if let Some(ref home_dir) = home_dir {
                     arg.replace("~", home_dir.to_str().unwrap())
                 } else {
@@ -161,11 +150,11 @@ impl Command {
                     && arg_chars[0] == '"'
                     && arg_chars[arg_chars.len() - 1] == '"'
                 {
-                    // quoted string
+                    // quoted string, never glob-expanded
                     let new_arg: String = arg_chars[1..arg_chars.len() - 1].iter().collect();
                     process = process.arg(new_arg);
                 } else {
-                    process = process.arg(arg.clone());
+                    process = push_glob_expanded_arg(process, &arg, cwd);
                 }
             }
         }
@@ -185,6 +174,10 @@ impl Command {
 
         if let Some(stdin) = stdin {
             process = process.stdin(stdin);
+        } else if let Some(objects) = live_objects {
+            if let Ok(stdin) = spawn_stream_stdin_writer(objects, self.stdin_format) {
+                process = process.stdin(stdin);
+            }
         }
 
         trace!(target: "nu::run::external", "set up stdin pipe");
@@ -199,18 +192,27 @@ impl Command {
             match stream_next {
                 StreamNext::Last => {
                     let _ = popen.detach();
-                    loop {
-                        match popen.poll() {
-                            None => {
-                                let _ = std::thread::sleep(std::time::Duration::new(0, 100000000));
-                            }
-                            _ => {
-                                let _ = popen.terminate();
-                                break;
-                            }
+                    match popen.wait() {
+                        Ok(status) if status.success() => Ok(ClassifiedInputStream::new()),
+                        // A nonzero exit is routine for plenty of externals
+                        // (`grep` with no match, `diff` with a difference), not
+                        // a reason to abort the rest of the pipeline, so report
+                        // it the same way a failed internal command does
+                        // (context.error) instead of bubbling a hard Err here.
+                        Ok(status) => {
+                            context.error(ShellError::labeled_error(
+                                format!("External command failed with {:?}", status),
+                                "command did not exit successfully",
+                                name_tag,
+                            ));
+                            Ok(ClassifiedInputStream::new())
                         }
+                        Err(err) => Err(ShellError::labeled_error(
+                            format!("External command failed to run: {}", err),
+                            "could not wait on command",
+                            name_tag,
+                        )),
                     }
-                    Ok(ClassifiedInputStream::new())
                 }
                 StreamNext::External => {
                     let _ = popen.detach();
@@ -222,8 +224,11 @@ impl Command {
                     let stdout = popen.stdout.take().unwrap();
                     let file = futures::io::AllowStdIo::new(stdout);
                     let stream = Framed::new(file, LinesCodec {});
-                    let stream = stream.map(move |line| {
-                        UntaggedValue::string(line.unwrap()).into_value(&name_tag)
+                    let stream = stream.map(move |line| match line.unwrap() {
+                        StreamedLine::Line(s) => UntaggedValue::string(s).into_value(&name_tag),
+                        StreamedLine::Binary(b) => {
+                            UntaggedValue::Primitive(Primitive::Binary(b)).into_value(&name_tag)
+                        }
                     });
                     Ok(ClassifiedInputStream::from_input_stream(
                         stream.boxed() as BoxStream<'static, Value>
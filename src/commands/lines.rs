@@ -13,6 +13,17 @@ impl WholeStreamCommand for Lines {
 
     fn signature(&self) -> Signature {
         Signature::build("lines")
+            .named(
+                "separator",
+                SyntaxShape::String,
+                "character or string to split on, defaults to newline",
+                Some('s'),
+            )
+            .switch(
+                "keep-empty",
+                "don't discard empty lines produced by the split",
+                None,
+            )
     }
 
     fn usage(&self) -> &str {
@@ -30,24 +41,56 @@ impl WholeStreamCommand for Lines {
 
 // TODO: "Amount remaining" wrapper
 
+/// Splits `s` into rows. With no custom separator this defers to `.lines()`
+/// so `\r\n` input doesn't retain a trailing `\r` on every row (same as
+/// before `--separator`/`--keep-empty` existed); a custom separator uses a
+/// plain `.split()`, whose trailing empty element from a separator-terminated
+/// input is dropped even under `--keep-empty`, since it isn't a real row.
+fn split_lines(s: &str, separator: Option<&str>, keep_empty: bool) -> Vec<String> {
+    let mut rows: Vec<String> = match separator {
+        None => s.lines().map(|s| s.to_string()).collect(),
+        Some(separator) => {
+            let mut parts: Vec<&str> = s.split(separator).collect();
+            if parts.last().map(|s| s.is_empty()).unwrap_or(false) {
+                parts.pop();
+            }
+            parts.into_iter().map(|s| s.to_string()).collect()
+        }
+    };
+
+    if !keep_empty {
+        rows.retain(|s| s.trim() != "");
+    }
+
+    rows
+}
+
 fn lines(args: CommandArgs, registry: &CommandRegistry) -> Result<OutputStream, ShellError> {
     let args = args.evaluate_once(registry)?;
     let tag = args.name_tag();
     let name_span = tag.span;
+
+    let separator = match args.get("separator") {
+        Some(sep) => Some(sep.as_string()?),
+        None => None,
+    };
+    let keep_empty = args.has("keep-empty");
+
     let input = args.input;
 
     let stream = input
         .values
         .map(move |v| match v.value {
             UntaggedValue::Primitive(Primitive::String(s)) => {
-                let split_result: Vec<_> = s.lines().filter(|s| s.trim() != "").collect();
+                let split_result =
+                    split_lines(&s, separator.as_ref().map(|s| s.as_str()), keep_empty);
 
                 trace!("split result = {:?}", split_result);
 
                 let mut result = VecDeque::new();
                 for s in split_result {
                     result.push_back(ReturnSuccess::value(
-                        UntaggedValue::Primitive(Primitive::String(s.into())).into_untagged_value(),
+                        UntaggedValue::Primitive(Primitive::String(s)).into_untagged_value(),
                     ));
                 }
                 result
@@ -6,11 +6,12 @@ use crate::parser::hir;
 use crate::prelude::*;
 use indexmap::IndexMap;
 use nu_protocol::{EvaluatedArgs, Scope, ShellError, Value};
+use std::rc::Rc;
 
 pub(crate) fn evaluate_args(
     call: &hir::Call,
     registry: &CommandRegistry,
-    scope: &Scope,
+    scope: &Rc<Scope>,
     source: &Text,
 ) -> Result<EvaluatedArgs, ShellError> {
     let positional: Result<Option<Vec<_>>, _> = call